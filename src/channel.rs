@@ -0,0 +1,172 @@
+//! A consumer channel layered on top of [`BatchRingBuffer`]: instead of
+//! spinning on `is_ready`, [`BatchReceiver::recv`] parks the calling thread
+//! and [`BatchReceiver::poll_recv`] registers a [`Waker`] for async
+//! consumers. Producers wake one parked waiter whenever `append` fills a
+//! batch, following the waiter/notify pattern used by thingbuf and
+//! ring-channel.
+//!
+//! This module needs thread parking and is only available with the `std`
+//! feature.
+
+use std::sync::{Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::vec::Vec;
+
+use crate::BatchRingBuffer;
+
+#[derive(Default)]
+struct WaitlistState {
+    parked: usize,
+    wakers: Vec<Waker>,
+}
+
+/// Parked thread/`Waker` handles waiting on the next batch to become ready.
+#[derive(Default)]
+pub(crate) struct Waitlist {
+    state: Mutex<WaitlistState>,
+    condvar: Condvar,
+}
+
+impl Waitlist {
+    pub(crate) fn new() -> Waitlist {
+        Waitlist::default()
+    }
+
+    /// Called by a producer once a batch becomes ready (every slot's stamp
+    /// published, see `TensorBatch::is_ready`). Wakes at
+    /// most one blocked thread and one registered `Waker`; the rest will
+    /// simply see the batch already consumed and go back to waiting.
+    pub(crate) fn notify_one(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.parked > 0 {
+            self.condvar.notify_one();
+        }
+        if let Some(waker) = state.wakers.pop() {
+            waker.wake();
+        }
+    }
+
+    fn park(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.parked += 1;
+        // Bounded wait rather than an unconditional one: `recv` re-checks
+        // `take_ready_batch` on every wakeup, so a missed or spurious
+        // notification just costs one extra poll, not a stuck consumer.
+        let (mut state, _timed_out) = self
+            .condvar
+            .wait_timeout(state, std::time::Duration::from_millis(50))
+            .unwrap();
+        state.parked -= 1;
+    }
+
+    fn register(&self, waker: &Waker) {
+        let mut state = self.state.lock().unwrap();
+        if !state.wakers.iter().any(|w| w.will_wake(waker)) {
+            state.wakers.push(waker.clone());
+        }
+    }
+}
+
+/// A consumer over a [`BatchRingBuffer`]'s completed batches.
+///
+/// `try_take` claims and reads a batch through
+/// [`BatchRingBuffer::take_ready_batch`], which is gated behind a single CAS
+/// on the ring's `tail`: whichever caller wins it is the only one that ever
+/// sees that batch's data, so any number of `BatchReceiver`s (or threads
+/// sharing one, via `recv`/`poll_recv` taking `&self`) can safely drive the
+/// same ring concurrently — each ready batch is delivered to exactly one of
+/// them, never duplicated or torn.
+pub struct BatchReceiver<'a> {
+    ring: &'a BatchRingBuffer,
+}
+
+impl<'a> BatchReceiver<'a> {
+    pub fn new(ring: &'a BatchRingBuffer) -> BatchReceiver<'a> {
+        BatchReceiver { ring }
+    }
+
+    /// Block the calling thread until a batch is ready, then consume it.
+    pub fn recv(&self) -> Vec<u8> {
+        loop {
+            if let Some(data) = self.try_take() {
+                return data;
+            }
+            self.ring.waiters.park();
+        }
+    }
+
+    /// Poll for the next ready batch without blocking, for async consumers.
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Vec<u8>> {
+        if let Some(data) = self.try_take() {
+            return Poll::Ready(data);
+        }
+        self.ring.waiters.register(cx.waker());
+        // Re-check after registering: a batch may have become ready between
+        // the check above and the registration, and its `notify_one` would
+        // otherwise have been missed.
+        match self.try_take() {
+            Some(data) => Poll::Ready(data),
+            None => Poll::Pending,
+        }
+    }
+
+    fn try_take(&self) -> Option<Vec<u8>> {
+        self.ring.take_ready_batch().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BatchRingBuffer;
+    use std::ptr;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    fn noop_waker() -> Waker {
+        fn clone(_: *const ()) -> std::task::RawWaker {
+            raw_waker()
+        }
+        fn no_op(_: *const ()) {}
+        fn raw_waker() -> std::task::RawWaker {
+            static VTABLE: std::task::RawWakerVTable =
+                std::task::RawWakerVTable::new(clone, no_op, no_op, no_op);
+            std::task::RawWaker::new(ptr::null(), &VTABLE)
+        }
+        unsafe { Waker::from_raw(raw_waker()) }
+    }
+
+    #[test]
+    fn recv_blocks_until_a_batch_is_appended() {
+        // `take_ready_batch` only claims a batch once `head` has moved past
+        // it, so the ring needs a second append (into the next buffer)
+        // before the first one becomes visible to the receiver.
+        let ring = Arc::new(BatchRingBuffer::new(2, 2, 1));
+        let consumer_ring = ring.clone();
+        let handle = thread::spawn(move || BatchReceiver::new(&consumer_ring).recv());
+
+        // Give the receiver a chance to park before the batch is ready.
+        thread::sleep(Duration::from_millis(10));
+        ring.append(&[1, 2]).unwrap();
+        ring.append(&[3, 4]).unwrap();
+
+        assert_eq!(handle.join().unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    fn poll_recv_is_pending_then_ready_once_appended() {
+        let ring = BatchRingBuffer::new(2, 2, 1);
+        let receiver = BatchReceiver::new(&ring);
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(receiver.poll_recv(&mut cx), Poll::Pending);
+
+        ring.append(&[5, 6]).unwrap();
+        assert_eq!(receiver.poll_recv(&mut cx), Poll::Pending);
+
+        ring.append(&[7, 8]).unwrap();
+        assert_eq!(receiver.poll_recv(&mut cx), Poll::Ready(vec![5, 6]));
+    }
+}
@@ -1,8 +1,51 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+mod channel;
+#[cfg(feature = "std")]
+mod sink;
+mod sync;
 mod tensor_batch;
-use std::sync::atomic::{AtomicUsize, Ordering};
 
+#[cfg(feature = "std")]
+pub use crate::channel::BatchReceiver;
+#[cfg(feature = "std")]
+pub use crate::sink::{BatchSink, BatchSinkReader};
+
+use crate::sync::{AtomicUsize, Ordering};
 use crate::tensor_batch::AppendError;
 
+// `debug_println!` needs `std`; under `no_std` the collision/retry traces are
+// simply dropped instead of being routed anywhere.
+#[cfg(feature = "std")]
+macro_rules! debug_println {
+    ($($arg:tt)*) => { std::println!($($arg)*) };
+}
+#[cfg(not(feature = "std"))]
+macro_rules! debug_println {
+    ($($arg:tt)*) => {};
+}
+
+/// Outcome of a [`BatchRingBuffer::force_append`] call: whether it found
+/// room on its own, or had to reclaim the oldest batch to make room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForceAppendOutcome {
+    Pushed(usize),
+    Dropped {
+        old_batch_idx: usize,
+        new_batch_idx: usize,
+    },
+}
+
 pub struct BatchRingBuffer {
     buffer: Vec<tensor_batch::TensorBatch>,
     tail: AtomicUsize,
@@ -24,6 +67,11 @@ pub struct BatchRingBuffer {
     // head = 5  → 5 & 0b0011 = 1
     // head = 6  → 6 & 0b0011 = 2
     mask: usize,
+    // Parked consumers (threads and `Waker`s) waiting on the next ready
+    // batch; see `channel::BatchReceiver`. Only needed with `std`, since
+    // thread parking isn't available otherwise.
+    #[cfg(feature = "std")]
+    waiters: channel::Waitlist,
 }
 
 fn is_power_of_two(n: usize) -> bool {
@@ -45,6 +93,52 @@ impl BatchRingBuffer {
             tail: AtomicUsize::new(0),
             head: AtomicUsize::new(0),
             mask: batches_nr - 1,
+            #[cfg(feature = "std")]
+            waiters: channel::Waitlist::new(),
+        }
+    }
+
+    /// Atomically claim the tail buffer if it's ready, copy its data out, and
+    /// reset it for reuse.
+    ///
+    /// Readiness is peeked before the CAS on `tail`, but the data is read
+    /// strictly after this wins that CAS, not before: reading the tail
+    /// buffer first and only CAS-ing afterwards (as a separate step) would let
+    /// [`Self::force_append`]'s own reclaim CAS win that race in between,
+    /// reset the buffer out from under an in-flight read, and hand the
+    /// caller back stale or half-overwritten bytes. Gating the read behind
+    /// the same CAS `force_append` and [`Self::consume_buffer`] use means
+    /// whichever of them wins is the only one that ever touches this
+    /// buffer's contents.
+    pub(crate) fn take_ready_batch(&self) -> Result<Vec<u8>, AppendError> {
+        let tail = self.tail.load(Ordering::Acquire);
+        let head = self.head.load(Ordering::Acquire);
+        if tail == head {
+            return Err(AppendError::NoBufferReady);
+        }
+
+        let batch = &self.buffer[tail];
+        if !batch.is_ready() {
+            return Err(AppendError::NoBufferReady);
+        }
+
+        let new_tail = (tail + 1) & self.mask;
+        match self
+            .tail
+            .compare_exchange_weak(tail, new_tail, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => {
+                let data = batch.get_data().expect(
+                    "is_ready() was observed true before the CAS, and only the CAS winner \
+                     resets the batch",
+                );
+                batch.reset();
+                Ok(data)
+            }
+            Err(_) => {
+                debug_println!("Collision");
+                Err(AppendError::NoBufferReady)
+            }
         }
     }
 
@@ -62,15 +156,24 @@ impl BatchRingBuffer {
 
             // Try to append to current buffer
             match buffer.append(data) {
-                Some(()) => return Ok(buffer_idx),
+                Some(()) => {
+                    // Wake a parked consumer if this append just filled the
+                    // batch; harmless if it was already ready, or if nobody
+                    // is waiting.
+                    #[cfg(feature = "std")]
+                    if buffer.is_ready() {
+                        self.waiters.notify_one();
+                    }
+                    return Ok(buffer_idx);
+                }
                 None => {
                     // Buffer is full, try to move to next
                     let tail = self.tail.load(Ordering::Acquire);
 
                     // Check if we have space (at least one buffer available)
                     let new_head = (head + 1) & self.mask;
-                    println!("mask {} {}", self.mask, (head + 1));
-                    println!("buffer full, moving up {head} to {new_head})(tail: {tail})");
+                    debug_println!("mask {} {}", self.mask, (head + 1));
+                    debug_println!("buffer full, moving up {head} to {new_head})(tail: {tail})");
                     if tail == new_head {
                         return Err(AppendError::AllBuffersFull);
                     }
@@ -87,7 +190,7 @@ impl BatchRingBuffer {
                             continue;
                         }
                         Err(_) => {
-                            println!("Collision");
+                            debug_println!("Collision");
                             // Another thread moved head, retry with new head
                             continue;
                         }
@@ -97,13 +200,98 @@ impl BatchRingBuffer {
         }
     }
 
+    /// Reserve the next slot across the ring, moving to the next buffer if
+    /// the current one is full, and hand back a guard that writes directly
+    /// into the batch's backing storage. Mirrors `append`'s retry loop.
+    pub fn reserve(&self) -> Result<tensor_batch::TensorWriteRef<'_>, AppendError> {
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            let buffer_idx = head & self.mask;
+            let buffer = &self.buffer[buffer_idx];
+
+            match buffer.reserve() {
+                Some(write_ref) => return Ok(write_ref),
+                None => {
+                    let tail = self.tail.load(Ordering::Acquire);
+
+                    let new_head = (head + 1) & self.mask;
+                    if tail == new_head {
+                        return Err(AppendError::AllBuffersFull);
+                    }
+
+                    match self.head.compare_exchange_weak(
+                        head,
+                        new_head,
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                    ) {
+                        Ok(_) => continue,
+                        Err(_) => {
+                            debug_println!("Collision");
+                            continue;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Like [`Self::append`], but when every buffer is full it reclaims the
+    /// oldest one (advancing `tail`) instead of returning
+    /// `AppendError::AllBuffersFull`, trading durability for never blocking
+    /// or erroring on streaming/telemetry workloads.
+    ///
+    /// The reclaim is gated behind the same CAS on `tail` that
+    /// [`Self::take_ready_batch`] and [`Self::consume_buffer`] use: only the
+    /// thread that wins it resets the victim batch, so a consumer that has
+    /// already claimed that exact batch (by winning the CAS first) can never
+    /// have it reclaimed out from under an in-flight read — the reclaim
+    /// simply loses the CAS and retries against whatever `tail` becomes.
+    pub fn force_append(&self, data: &[u8]) -> Result<ForceAppendOutcome, AppendError> {
+        let mut dropped_idx = None;
+        loop {
+            match self.append(data) {
+                Ok(new_batch_idx) => {
+                    return Ok(match dropped_idx {
+                        Some(old_batch_idx) => ForceAppendOutcome::Dropped {
+                            old_batch_idx,
+                            new_batch_idx,
+                        },
+                        None => ForceAppendOutcome::Pushed(new_batch_idx),
+                    });
+                }
+                Err(AppendError::AllBuffersFull) => {
+                    let tail = self.tail.load(Ordering::Acquire);
+                    let new_tail = (tail + 1) & self.mask;
+
+                    match self.tail.compare_exchange_weak(
+                        tail,
+                        new_tail,
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                    ) {
+                        Ok(_) => {
+                            self.buffer[tail].reset();
+                            dropped_idx = Some(tail);
+                        }
+                        Err(_) => {
+                            debug_println!("Collision");
+                        }
+                    }
+                }
+                // `append` never returns this; it's only ever produced by
+                // `consume_buffer` and `take_ready_batch`.
+                Err(err @ AppendError::NoBufferReady) => return Err(err),
+            }
+        }
+    }
+
     pub fn consume_buffer(&self) -> Result<(), AppendError> {
         let tail = self.tail.load(Ordering::Acquire);
         let head = self.head.load(Ordering::Acquire);
         if tail == head {
             return Err(AppendError::NoBufferReady);
         }
-        self.buffer[tail].reset();
 
         let new_tail = (tail + 1) & self.mask;
         match self
@@ -111,14 +299,18 @@ impl BatchRingBuffer {
             .compare_exchange_weak(tail, new_tail, Ordering::AcqRel, Ordering::Acquire)
         {
             Ok(_) => {
-                // Successfully moved to next buffer, retry append
-                
-                return Ok(());
+                // Reset only now that our CAS on `tail` has won: resetting
+                // before the CAS would let a second consumer (or a
+                // force-overwriting producer) that loaded the same `tail`
+                // reset this same batch too, even though only one of them
+                // actually reclaims it.
+                self.buffer[tail].reset();
+                Ok(())
             }
             Err(_) => {
-                println!("Collision");
+                debug_println!("Collision");
                 // Another thread moved head, retry with new head
-                return Err(AppendError::NoBufferReady);
+                Err(AppendError::NoBufferReady)
             }
         }
     }
@@ -144,4 +336,100 @@ mod tests {
         // let final_data = stacked_tensors.get_data().unwrap();
         // assert_eq!(final_data, &data)
     }
+
+    #[test]
+    fn reserve_writes_through_and_moves_to_next_buffer_when_full() {
+        let tensor_size = 2;
+        let capacity = 1;
+        let ringbatch = BatchRingBuffer::new(2, tensor_size, capacity);
+
+        let mut slot = ringbatch.reserve().unwrap();
+        slot.copy_from_slice(&[1, 2]);
+        drop(slot);
+
+        // First buffer is now full; reserve must move on to the next one
+        // instead of returning AllBuffersFull.
+        let mut slot = ringbatch.reserve().unwrap();
+        slot.copy_from_slice(&[3, 4]);
+        drop(slot);
+
+        ringbatch.consume_buffer().unwrap();
+    }
+
+    #[test]
+    fn force_append_pushes_while_space_remains() {
+        let ringbatch = BatchRingBuffer::new(2, 1, 1);
+        assert_eq!(
+            ringbatch.force_append(&[1]).unwrap(),
+            ForceAppendOutcome::Pushed(0)
+        );
+        assert_eq!(
+            ringbatch.force_append(&[2]).unwrap(),
+            ForceAppendOutcome::Pushed(1)
+        );
+    }
+
+    #[test]
+    fn force_append_drops_oldest_when_full() {
+        let ringbatch = BatchRingBuffer::new(2, 1, 1);
+        ringbatch.force_append(&[1]).unwrap();
+        ringbatch.force_append(&[2]).unwrap();
+
+        // Both buffers are now full with no consumer having run, so
+        // force_append must reclaim the oldest one (index 0) instead of
+        // erroring like `append` would.
+        let outcome = ringbatch.force_append(&[3]).unwrap();
+        assert_eq!(
+            outcome,
+            ForceAppendOutcome::Dropped {
+                old_batch_idx: 0,
+                new_batch_idx: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn force_append_never_reclaims_a_batch_a_consumer_is_reading() {
+        use std::collections::HashSet;
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+
+        // Every batch written gets a unique byte so a consumer that observes
+        // the same value twice proves a reclaim raced past a
+        // take_ready_batch claim.
+        let ringbatch = Arc::new(BatchRingBuffer::new(2, 1, 1));
+        let seen = Arc::new(Mutex::new(HashSet::new()));
+
+        let producer = {
+            let ringbatch = ringbatch.clone();
+            thread::spawn(move || {
+                for i in 0u8..200 {
+                    let _ = ringbatch.force_append(&[i]);
+                }
+            })
+        };
+
+        let consumers: Vec<_> = (0..4)
+            .map(|_| {
+                let ringbatch = ringbatch.clone();
+                let seen = seen.clone();
+                thread::spawn(move || {
+                    for _ in 0..200 {
+                        if let Ok(data) = ringbatch.take_ready_batch() {
+                            assert!(
+                                seen.lock().unwrap().insert(data[0]),
+                                "batch {} was delivered to more than one consumer",
+                                data[0]
+                            );
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        producer.join().unwrap();
+        for consumer in consumers {
+            consumer.join().unwrap();
+        }
+    }
 }
@@ -0,0 +1,240 @@
+//! Sender-pays spill-to-disk sink, in the style of shardio: whichever
+//! thread calls [`BatchSink::flush`] compresses and appends the batch to
+//! disk itself — no background writer or compression worker is spawned.
+//! `consume_buffer` and `BatchReceiver` do not call the sink; they reset the
+//! batch or hand back its bytes as a `Vec<u8>`. Segments are self-describing, and a
+//! trailing index of offsets is written on close so they can be range-read
+//! or merged later without scanning the whole file.
+//!
+//! Requires `std` for file IO.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::vec::Vec;
+
+use crate::tensor_batch::TensorBatch;
+
+const SEGMENT_HEADER_LEN: usize = 8 * 4;
+const FOOTER_LEN: usize = 8 * 2;
+
+/// Fixed-size record written before each segment's compressed payload, so a
+/// segment can be decoded without consulting anything else in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SegmentHeader {
+    tensor_size: u64,
+    capacity: u64,
+    written_count: u64,
+    compressed_len: u64,
+}
+
+impl SegmentHeader {
+    fn to_bytes(self) -> [u8; SEGMENT_HEADER_LEN] {
+        let mut out = [0u8; SEGMENT_HEADER_LEN];
+        out[0..8].copy_from_slice(&self.tensor_size.to_le_bytes());
+        out[8..16].copy_from_slice(&self.capacity.to_le_bytes());
+        out[16..24].copy_from_slice(&self.written_count.to_le_bytes());
+        out[24..32].copy_from_slice(&self.compressed_len.to_le_bytes());
+        out
+    }
+
+    fn from_bytes(bytes: [u8; SEGMENT_HEADER_LEN]) -> SegmentHeader {
+        SegmentHeader {
+            tensor_size: u64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            capacity: u64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+            written_count: u64::from_le_bytes(bytes[16..24].try_into().unwrap()),
+            compressed_len: u64::from_le_bytes(bytes[24..32].try_into().unwrap()),
+        }
+    }
+}
+
+/// Appends consumed [`TensorBatch`]es to disk as compressed segments.
+/// `flush` does the compression and the write on the calling thread, so the
+/// consumer that drains a batch is the one that pays for archiving it.
+pub struct BatchSink {
+    writer: BufWriter<File>,
+    segment_offsets: Vec<u64>,
+    next_offset: u64,
+}
+
+impl BatchSink {
+    pub fn new(path: impl AsRef<Path>) -> io::Result<BatchSink> {
+        let file = File::create(path)?;
+        Ok(BatchSink {
+            writer: BufWriter::new(file),
+            segment_offsets: Vec::new(),
+            next_offset: 0,
+        })
+    }
+
+    /// Compress `batch`'s data and append it as one segment.
+    pub fn flush(&mut self, batch: &TensorBatch) -> io::Result<()> {
+        let data = batch.get_data().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "batch is not fully written")
+        })?;
+        let compressed = lz4_flex::compress_prepend_size(&data);
+
+        let header = SegmentHeader {
+            tensor_size: batch.tensor_size() as u64,
+            capacity: batch.capacity() as u64,
+            written_count: batch.written_count() as u64,
+            compressed_len: compressed.len() as u64,
+        };
+
+        self.writer.write_all(&header.to_bytes())?;
+        self.writer.write_all(&compressed)?;
+
+        self.segment_offsets.push(self.next_offset);
+        self.next_offset += SEGMENT_HEADER_LEN as u64 + compressed.len() as u64;
+        Ok(())
+    }
+
+    /// Write the trailing index of segment offsets and close the file.
+    pub fn close(mut self) -> io::Result<()> {
+        let index_offset = self.next_offset;
+        for offset in &self.segment_offsets {
+            self.writer.write_all(&offset.to_le_bytes())?;
+        }
+        self.writer.write_all(&index_offset.to_le_bytes())?;
+        self.writer
+            .write_all(&(self.segment_offsets.len() as u64).to_le_bytes())?;
+        self.writer.flush()
+    }
+}
+
+/// Reads segments written by a [`BatchSink`] back into tensor batch byte
+/// buffers, range-reading each one off the trailing index rather than
+/// scanning the file from the start.
+pub struct BatchSinkReader {
+    reader: BufReader<File>,
+    segment_offsets: Vec<u64>,
+}
+
+impl BatchSinkReader {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<BatchSinkReader> {
+        let mut file = File::open(path)?;
+
+        file.seek(SeekFrom::End(-(FOOTER_LEN as i64)))?;
+        let mut footer = [0u8; FOOTER_LEN];
+        file.read_exact(&mut footer)?;
+        let index_offset = u64::from_le_bytes(footer[0..8].try_into().unwrap());
+        let segment_count = u64::from_le_bytes(footer[8..16].try_into().unwrap()) as usize;
+
+        file.seek(SeekFrom::Start(index_offset))?;
+        let mut segment_offsets = Vec::with_capacity(segment_count);
+        for _ in 0..segment_count {
+            let mut offset_bytes = [0u8; 8];
+            file.read_exact(&mut offset_bytes)?;
+            segment_offsets.push(u64::from_le_bytes(offset_bytes));
+        }
+
+        Ok(BatchSinkReader {
+            reader: BufReader::new(file),
+            segment_offsets,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.segment_offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segment_offsets.is_empty()
+    }
+
+    /// Range-read and decompress the `idx`th segment back into tensor bytes.
+    pub fn read_segment(&mut self, idx: usize) -> io::Result<Vec<u8>> {
+        let offset = *self.segment_offsets.get(idx).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "segment index out of range")
+        })?;
+
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let mut header_bytes = [0u8; SEGMENT_HEADER_LEN];
+        self.reader.read_exact(&mut header_bytes)?;
+        let header = SegmentHeader::from_bytes(header_bytes);
+
+        let mut compressed = vec![0u8; header.compressed_len as usize];
+        self.reader.read_exact(&mut compressed)?;
+
+        lz4_flex::decompress_size_prepended(&compressed)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Iterate all segments back into tensor batch byte buffers, in the
+    /// order they were written.
+    pub fn iter_batches(&mut self) -> impl Iterator<Item = io::Result<Vec<u8>>> + '_ {
+        (0..self.len()).map(move |idx| self.read_segment(idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor_batch::TensorBatch;
+    use std::path::PathBuf;
+
+    /// A unique path under the system temp dir, removed when the guard drops.
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> TempPath {
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos();
+            TempPath(std::env::temp_dir().join(format!(
+                "stacktensor_sink_test_{name}_{}_{nanos}",
+                std::process::id()
+            )))
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn round_trips_a_batch_through_sink_and_reader() {
+        let tensor_size = 4;
+        let capacity = 2;
+        let batch = TensorBatch::new(tensor_size, capacity);
+        batch.append(&[1, 2, 3, 4]);
+        batch.append(&[5, 6, 7, 8]);
+        assert!(batch.is_ready());
+
+        let path = TempPath::new("roundtrip");
+        let mut sink = BatchSink::new(&path.0).unwrap();
+        sink.flush(&batch).unwrap();
+        sink.close().unwrap();
+
+        let mut reader = BatchSinkReader::open(&path.0).unwrap();
+        assert_eq!(reader.len(), 1);
+        assert_eq!(
+            reader.read_segment(0).unwrap(),
+            vec![1, 2, 3, 4, 5, 6, 7, 8]
+        );
+    }
+
+    #[test]
+    fn round_trips_multiple_segments_in_write_order() {
+        let tensor_size = 2;
+        let capacity = 1;
+
+        let first = TensorBatch::new(tensor_size, capacity);
+        first.append(&[1, 2]);
+        let second = TensorBatch::new(tensor_size, capacity);
+        second.append(&[3, 4]);
+
+        let path = TempPath::new("multi_segment");
+        let mut sink = BatchSink::new(&path.0).unwrap();
+        sink.flush(&first).unwrap();
+        sink.flush(&second).unwrap();
+        sink.close().unwrap();
+
+        let mut reader = BatchSinkReader::open(&path.0).unwrap();
+        let batches: io::Result<Vec<Vec<u8>>> = reader.iter_batches().collect();
+        assert_eq!(batches.unwrap(), vec![vec![1, 2], vec![3, 4]]);
+    }
+}
@@ -0,0 +1,25 @@
+//! Atomic/cell primitives abstracted behind a loom-style shim (as
+//! concurrent-queue does), so the rest of the crate is written once against
+//! `crate::sync::{AtomicUsize, Ordering, UnsafeCell}` and still runs:
+//! - under `loom`, for model checking (`loom` feature),
+//! - on targets without native CAS, via the `portable-atomic` crate
+//!   (`portable-atomic` feature),
+//! - everywhere else, via `core::sync::atomic` / `core::cell`.
+//!
+//! `loom` takes priority if both features are enabled, since model checking
+//! needs loom's own instrumented types regardless of target.
+
+#[cfg(feature = "loom")]
+pub(crate) use loom::cell::UnsafeCell;
+#[cfg(feature = "loom")]
+pub(crate) use loom::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(all(not(feature = "loom"), feature = "portable-atomic"))]
+pub(crate) use core::cell::UnsafeCell;
+#[cfg(all(not(feature = "loom"), feature = "portable-atomic"))]
+pub(crate) use portable_atomic::{AtomicUsize, Ordering};
+
+#[cfg(all(not(feature = "loom"), not(feature = "portable-atomic")))]
+pub(crate) use core::cell::UnsafeCell;
+#[cfg(all(not(feature = "loom"), not(feature = "portable-atomic")))]
+pub(crate) use core::sync::atomic::{AtomicUsize, Ordering};
@@ -1,36 +1,37 @@
-#[cfg(feature = "loom")]
-use loom::cell::UnsafeCell;
-#[cfg(feature = "loom")]
-use loom::sync::atomic::{fence, AtomicUsize, Ordering};
+use core::ops::{Deref, DerefMut};
 
-use std::cell::RefCell;
+#[cfg(feature = "std")]
+use std::{boxed::Box, vec, vec::Vec};
 
-#[cfg(not(feature = "loom"))]
-use std::cell::UnsafeCell;
-#[cfg(not(feature = "loom"))]
-use std::sync::atomic::{fence, AtomicUsize, Ordering};
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, vec, vec::Vec};
+
+use crate::sync::{AtomicUsize, Ordering, UnsafeCell};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppendError {
     AllBuffersFull,
-}
-
-enum BatchState {
-    Writtable,
-    Written,
-    Consumed,
+    NoBufferReady,
 }
 
 pub struct TensorBatch {
-    #[cfg(feature = "loom")]
-    data: Vec<UnsafeCell<Vec<u8>>>,
-    #[cfg(not(feature = "loom"))]
-    data: UnsafeCell<Vec<u8>>,
+    // One cell per slot, not one cell for the whole buffer: disjoint slots
+    // are written by different threads concurrently (one per `reserve()`),
+    // and a single shared cell would make loom see those as overlapping
+    // mutable accesses to the same `UnsafeCell`, even though the byte
+    // ranges never actually overlap. Per-slot cells give loom one causality
+    // track per slot, so it can verify disjoint-slot writes are sound
+    // instead of rejecting them as a false positive.
+    data: Box<[UnsafeCell<Box<[u8]>>]>,
     capacity: usize,
     tensor_size: usize,
     reserved_slots: AtomicUsize,
-    written_slots: AtomicUsize,
-    state: RefCell<BatchState>,
+    // One publication stamp per slot, initialized to the slot's own index.
+    // `append` bumps `stamps[idx]` to `idx + 1` (Release) once the slot's
+    // bytes are written; a reader that observes `idx + 1` via an Acquire
+    // load is guaranteed to see those bytes. This gives a real per-slot
+    // happens-before edge, unlike the single batch-wide fence it replaces.
+    stamps: Vec<AtomicUsize>,
 }
 
 unsafe impl Send for TensorBatch {}
@@ -38,85 +39,69 @@ unsafe impl Sync for TensorBatch {}
 
 impl TensorBatch {
     pub fn new(tensor_size: usize, capacity: usize) -> TensorBatch {
-        #[cfg(feature = "loom")]
-        {
-            let mut data = Vec::with_capacity(capacity);
-            for _ in 0..capacity {
-                data.push(UnsafeCell::new(vec![0; tensor_size]))
-            }
-            TensorBatch {
-                data,
-                tensor_size,
-                capacity,
-                reserved_slots: AtomicUsize::new(0),
-                written_slots: AtomicUsize::new(0),
-                state: RefCell::new(BatchState::Writtable),
-            }
-        }
-        #[cfg(not(feature = "loom"))]
-        {
-            let data = UnsafeCell::new(vec![0; tensor_size * capacity]);
-            TensorBatch {
-                data,
-                tensor_size,
-                capacity,
-                reserved_slots: AtomicUsize::new(0),
-                written_slots: AtomicUsize::new(0),
-                state: RefCell::new(BatchState::Writtable),
-            }
+        let data = (0..capacity)
+            .map(|_| UnsafeCell::new(vec![0u8; tensor_size].into_boxed_slice()))
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        let stamps = (0..capacity).map(AtomicUsize::new).collect();
+        TensorBatch {
+            data,
+            tensor_size,
+            capacity,
+            reserved_slots: AtomicUsize::new(0),
+            stamps,
         }
     }
 
     pub fn append(&self, data: &[u8]) -> Option<()> {
         assert_eq!(data.len(), self.tensor_size);
+        let mut slot = self.reserve()?;
+        slot.copy_from_slice(data);
+        Some(())
+    }
+
+    /// Reserve the next slot and hand back a guard that writes directly into
+    /// the batch's backing storage, so callers can serialize a tensor in
+    /// place instead of building it elsewhere and copying it in through
+    /// `append`. The slot is published (its stamp bumped) when the guard is
+    /// dropped.
+    pub fn reserve(&self) -> Option<TensorWriteRef<'_>> {
         let idx = self.reserved_slots.fetch_add(1, Ordering::SeqCst);
         if idx >= self.capacity {
             return None;
         }
 
-        #[cfg(feature = "loom")]
-        {
-            self.data[idx].with_mut(|ptr| unsafe {
-                let slice = &mut (&mut (*ptr))[0..self.tensor_size];
-                slice.copy_from_slice(data);
-            });
-        }
+        let cell = &self.data[idx];
 
+        #[cfg(feature = "loom")]
+        let base = cell.with_mut(|ptr| ptr);
         #[cfg(not(feature = "loom"))]
-        unsafe {
-            let start = idx * self.tensor_size;
-            let end = (idx + 1) * self.tensor_size;
-
-            // Isolation of portions of the vector is guaranteed by reserved_slots atomics
-            let slice = &mut (&mut (*self.data.get()))[start..end];
-            slice.copy_from_slice(data);
-        }
-        fence(Ordering::Release);
+        let base = cell.get();
+
+        let ptr = unsafe { (*base).as_mut_ptr() };
+        Some(TensorWriteRef {
+            batch: self,
+            idx,
+            ptr,
+        })
+    }
 
-        // Mark this slot as written
-        let slot_written = self.written_slots.fetch_add(1, Ordering::Release);
-        if slot_written + 1 == self.capacity {
-            self.state.replace(BatchState::Written);
+    pub fn get_data(&self) -> Option<Vec<u8>> {
+        if !self.is_ready() {
+            return None;
         }
-        Some(())
-    }
 
-    pub fn get_data(&self) -> Option<&Vec<u8>> {
-        if self.written_slots.load(Ordering::Acquire) == self.capacity {
+        let mut out = Vec::with_capacity(self.tensor_size * self.capacity);
+        for cell in self.data.iter() {
             #[cfg(feature = "loom")]
-            {
-                // For loom, we can't safely return a reference
-                // This is a limitation - in real code you'd want proper synchronization
-                None
-            }
+            cell.with(|ptr| unsafe { out.extend_from_slice(&*ptr) });
 
             #[cfg(not(feature = "loom"))]
             unsafe {
-                Some(&*self.data.get())
+                out.extend_from_slice(&*cell.get());
             }
-        } else {
-            None
         }
+        Some(out)
     }
 
     /// Check if this buffer is full
@@ -129,21 +114,49 @@ impl TensorBatch {
         self.reserved_slots.load(Ordering::Acquire)
     }
 
-    /// Get the number of slots fully written
+    /// Get the number of slots fully written, each counted by checking its
+    /// stamp rather than trusting a single shared counter.
     pub fn written_count(&self) -> usize {
-        self.written_slots.load(Ordering::Acquire)
+        self.stamps
+            .iter()
+            .enumerate()
+            .filter(|(i, stamp)| stamp.load(Ordering::Acquire) == i + 1)
+            .count()
     }
 
     /// Check if all reserved slots have been written
     pub fn is_ready(&self) -> bool {
-        let written = self.written_slots.load(Ordering::Acquire);
-        written == self.capacity
+        self.written_count() == self.capacity
+    }
+
+    /// Read a single slot's bytes once it's been published, without waiting
+    /// for the rest of the batch to fill. Returns `None` if `idx` is out of
+    /// range or hasn't been written yet.
+    pub fn get_slot(&self, idx: usize) -> Option<&[u8]> {
+        let stamp = self.stamps.get(idx)?.load(Ordering::Acquire);
+        if stamp != idx + 1 {
+            return None;
+        }
+
+        let cell = &self.data[idx];
+
+        #[cfg(feature = "loom")]
+        let base = cell.with(|ptr| ptr);
+        #[cfg(not(feature = "loom"))]
+        let base = cell.get();
+
+        // SAFETY: the Acquire load above observing `idx + 1` happens-after
+        // the Release store in `TensorWriteRef::drop` that published this
+        // slot, so the bytes it wrote are visible here.
+        Some(unsafe { (*base).as_ref() })
     }
 
     /// Reset the buffer for reuse
     pub fn reset(&self) {
         self.reserved_slots.store(0, Ordering::Release);
-        self.written_slots.store(0, Ordering::Release);
+        for (i, stamp) in self.stamps.iter().enumerate() {
+            stamp.store(i, Ordering::Release);
+        }
     }
 
     /// Get the capacity of this buffer
@@ -157,6 +170,34 @@ impl TensorBatch {
     }
 }
 
+/// A reserved, not-yet-published slot in a [`TensorBatch`]. `Deref`/`DerefMut`
+/// expose the slot's `tensor_size` bytes for the caller to write in place;
+/// the slot is published (its stamp bumped) when this guard is dropped.
+pub struct TensorWriteRef<'a> {
+    batch: &'a TensorBatch,
+    idx: usize,
+    ptr: *mut u8,
+}
+
+impl Deref for TensorWriteRef<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.ptr, self.batch.tensor_size) }
+    }
+}
+
+impl DerefMut for TensorWriteRef<'_> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr, self.batch.tensor_size) }
+    }
+}
+
+impl Drop for TensorWriteRef<'_> {
+    fn drop(&mut self) {
+        self.batch.stamps[self.idx].store(self.idx + 1, Ordering::Release);
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -179,7 +220,62 @@ mod tests {
             stacked_tensors.append(&data[i * tensor_size..(i + 1) * tensor_size]);
         }
         let final_data = stacked_tensors.get_data().unwrap();
-        assert_eq!(final_data, &data)
+        assert_eq!(final_data, data)
+    }
+
+    #[cfg(not(feature = "loom"))]
+    #[test]
+    fn reserve_writes_through_deref_and_publishes_on_drop() {
+        let tensor_size = 4;
+        let capacity = 2;
+        let batch = TensorBatch::new(tensor_size, capacity);
+
+        let mut slot = batch.reserve().unwrap();
+        assert_eq!(slot.len(), tensor_size);
+        slot.copy_from_slice(&[1, 2, 3, 4]);
+
+        // Not published yet: the guard is still alive.
+        assert_eq!(batch.written_count(), 0);
+        assert!(!batch.is_ready());
+        drop(slot);
+
+        // Publishing happens on drop.
+        assert_eq!(batch.written_count(), 1);
+        assert!(batch.get_data().is_none());
+
+        let mut slot = batch.reserve().unwrap();
+        slot.copy_from_slice(&[5, 6, 7, 8]);
+        drop(slot);
+
+        assert!(batch.is_ready());
+        assert_eq!(batch.get_data().unwrap(), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[cfg(not(feature = "loom"))]
+    #[test]
+    fn reserve_past_capacity_returns_none() {
+        let batch = TensorBatch::new(4, 1);
+        let _slot = batch.reserve().unwrap();
+        assert!(batch.reserve().is_none());
+    }
+
+    #[cfg(not(feature = "loom"))]
+    #[test]
+    fn get_slot_reads_individual_slots_before_the_batch_is_ready() {
+        let tensor_size = 2;
+        let capacity = 2;
+        let batch = TensorBatch::new(tensor_size, capacity);
+
+        assert_eq!(batch.get_slot(0), None);
+
+        batch.append(&[1, 2]);
+        assert_eq!(batch.get_slot(0), Some(&[1, 2][..]));
+        // Slot 1 hasn't been written yet, and the batch as a whole isn't
+        // ready, but slot 0 is already readable.
+        assert_eq!(batch.get_slot(1), None);
+        assert!(!batch.is_ready());
+
+        assert_eq!(batch.get_slot(2), None);
     }
 
     #[test]